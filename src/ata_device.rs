@@ -0,0 +1,216 @@
+use alloc::vec::Vec;
+use core::ffi::c_void;
+
+use crate::{
+    ata_passthru::{
+        AtaCommandBlock, AtaPassThru, AtaPassThruCommandPacket, AtaPassThruDirection, AtaStatusBlock,
+        ATA_CMD_IDENTIFY_DEVICE, ATA_CMD_TRUSTED_RECEIVE, ATA_CMD_TRUSTED_SEND,
+    },
+    error::{Error, OpalError, Result},
+    opal::StatusCode,
+    secure_device::SecureDeviceIo,
+};
+
+/// TCG Opal over SATA/eSATA, via the ATA TRUSTED SEND/RECEIVE commands
+/// (ATA8-ACS, opcodes 0x5E/0x5C) carrying the same ComPackets NVMe's
+/// Security Send/Receive does.
+///
+/// `proto` is a raw pointer rather than a borrow because the `AtaPassThru`
+/// it points at is opened once in `find_secure_devices` and kept open for
+/// the rest of boot services, well past the scope that looked it up.
+pub struct AtaDevice {
+    proto: *mut AtaPassThru,
+    port: u16,
+    port_multiplier_port: u16,
+    serial_num: Vec<u8>,
+}
+
+impl AtaDevice {
+    /// Safety: `proto` must point to a live `AtaPassThru` that outlives
+    /// this `AtaDevice`.
+    pub unsafe fn new(proto: *mut AtaPassThru, port: u16, port_multiplier_port: u16) -> Result<Self> {
+        let serial_num = identify_serial_num(&mut *proto, port, port_multiplier_port)?;
+        Ok(Self {
+            proto,
+            port,
+            port_multiplier_port,
+            serial_num,
+        })
+    }
+
+    fn proto(&mut self) -> &mut AtaPassThru {
+        unsafe { &mut *self.proto }
+    }
+
+    fn trusted_command(
+        &mut self,
+        command: u8,
+        protocol_id: u8,
+        com_id: u16,
+        buf: &mut [u8],
+        direction: AtaPassThruDirection,
+    ) -> Result<usize> {
+        // TRUSTED SEND/RECEIVE only transfers whole 512-byte sectors, so pad
+        // `buf` up to a sector boundary and send that instead; sector_count
+        // must match the buffer we actually hand to pass_thru, not `buf`'s
+        // original length.
+        let sector_count = ((buf.len() + 511) / 512).max(1) as u8;
+        let mut padded = vec![0u8; sector_count as usize * 512];
+        padded[..buf.len()].copy_from_slice(buf);
+
+        let mut acb = AtaCommandBlock {
+            reserved1: [0; 4],
+            feature: protocol_id,
+            feature_exp: 0,
+            sector_count,
+            sector_count_exp: 0,
+            sector_number: (com_id & 0xff) as u8,
+            sector_number_exp: 0,
+            cylinder_low: ((com_id >> 8) & 0xff) as u8,
+            cylinder_low_exp: 0,
+            cylinder_high: 0,
+            cylinder_high_exp: 0,
+            device_head: 0xe0,
+            command,
+            reserved2: [0; 3],
+        };
+        let mut asb = AtaStatusBlock {
+            reserved1: [0; 2],
+            error: 0,
+            sector_count: 0,
+            sector_count_exp: 0,
+            sector_number: 0,
+            sector_number_exp: 0,
+            cylinder_low: 0,
+            cylinder_low_exp: 0,
+            cylinder_high: 0,
+            cylinder_high_exp: 0,
+            device_head: 0,
+            status: 0,
+            reserved2: [0; 2],
+        };
+
+        let mut packet = AtaPassThruCommandPacket {
+            asb: &mut asb,
+            acb: &mut acb,
+            timeout: 0,
+            in_data_buffer: core::ptr::null_mut(),
+            out_data_buffer: core::ptr::null_mut(),
+            in_transfer_length: 0,
+            out_transfer_length: 0,
+            protocol: 0,
+            length: 0,
+        };
+
+        match direction {
+            AtaPassThruDirection::Read => {
+                packet.in_data_buffer = padded.as_mut_ptr() as *mut c_void;
+                packet.in_transfer_length = padded.len() as u32;
+            }
+            AtaPassThruDirection::Write => {
+                packet.out_data_buffer = padded.as_mut_ptr() as *mut c_void;
+                packet.out_transfer_length = padded.len() as u32;
+            }
+            AtaPassThruDirection::None => {}
+        }
+
+        let (port, port_multiplier_port) = (self.port, self.port_multiplier_port);
+        unsafe { self.proto().pass_thru(port, port_multiplier_port, &mut packet) }
+            .map_err(|_| Error::Opal(OpalError::Status(StatusCode::Fail)))?;
+
+        if asb.error != 0 {
+            return Err(Error::Opal(OpalError::Status(StatusCode::Fail)));
+        }
+
+        if direction == AtaPassThruDirection::Read {
+            buf.copy_from_slice(&padded[..buf.len()]);
+        }
+        Ok(buf.len())
+    }
+}
+
+impl SecureDeviceIo for AtaDevice {
+    fn security_send(&mut self, protocol_id: u8, com_id: u16, buf: &[u8]) -> Result {
+        let mut buf = buf.to_vec();
+        self.trusted_command(
+            ATA_CMD_TRUSTED_SEND,
+            protocol_id,
+            com_id,
+            &mut buf,
+            AtaPassThruDirection::Write,
+        )
+        .map(|_| ())
+    }
+
+    fn security_recv(&mut self, protocol_id: u8, com_id: u16, buf: &mut [u8]) -> Result<usize> {
+        self.trusted_command(
+            ATA_CMD_TRUSTED_RECEIVE,
+            protocol_id,
+            com_id,
+            buf,
+            AtaPassThruDirection::Read,
+        )
+    }
+
+    fn serial_num(&self) -> &[u8] {
+        &self.serial_num
+    }
+}
+
+fn identify_serial_num(proto: &mut AtaPassThru, port: u16, port_multiplier_port: u16) -> Result<Vec<u8>> {
+    let mut data = vec![0u8; 512];
+
+    let mut acb = AtaCommandBlock {
+        reserved1: [0; 4],
+        feature: 0,
+        feature_exp: 0,
+        sector_count: 1,
+        sector_count_exp: 0,
+        sector_number: 0,
+        sector_number_exp: 0,
+        cylinder_low: 0,
+        cylinder_low_exp: 0,
+        cylinder_high: 0,
+        cylinder_high_exp: 0,
+        device_head: 0xe0,
+        command: ATA_CMD_IDENTIFY_DEVICE,
+        reserved2: [0; 3],
+    };
+    let mut asb = AtaStatusBlock {
+        reserved1: [0; 2],
+        error: 0,
+        sector_count: 0,
+        sector_count_exp: 0,
+        sector_number: 0,
+        sector_number_exp: 0,
+        cylinder_low: 0,
+        cylinder_low_exp: 0,
+        cylinder_high: 0,
+        cylinder_high_exp: 0,
+        device_head: 0,
+        status: 0,
+        reserved2: [0; 2],
+    };
+    let mut packet = AtaPassThruCommandPacket {
+        asb: &mut asb,
+        acb: &mut acb,
+        timeout: 0,
+        in_data_buffer: data.as_mut_ptr() as *mut c_void,
+        out_data_buffer: core::ptr::null_mut(),
+        in_transfer_length: data.len() as u32,
+        out_transfer_length: 0,
+        protocol: 0,
+        length: 0,
+    };
+
+    unsafe { proto.pass_thru(port, port_multiplier_port, &mut packet) }
+        .map_err(|_| Error::Opal(OpalError::Status(StatusCode::Fail)))?;
+
+    // IDENTIFY DEVICE: words 10..20 (bytes 20..40) hold the serial number,
+    // ASCII with each pair of bytes byte-swapped.
+    let mut serial = data[20..40].to_vec();
+    for pair in serial.chunks_exact_mut(2) {
+        pair.swap(0, 1);
+    }
+    Ok(serial)
+}
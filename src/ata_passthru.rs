@@ -0,0 +1,111 @@
+//! Minimal binding for `EFI_ATA_PASS_THRU_PROTOCOL`, mirroring
+//! `nvme_passthru` but for SATA/eSATA drives: enough of the protocol to
+//! issue ATA TRUSTED SEND/RECEIVE (SECURITY SEND/RECEIVE to TCG Opal) and
+//! IDENTIFY DEVICE.
+
+use core::ffi::c_void;
+
+use uefi::{proto::unsafe_protocol, Status};
+
+pub const ATA_CMD_TRUSTED_RECEIVE: u8 = 0x5C;
+pub const ATA_CMD_TRUSTED_SEND: u8 = 0x5E;
+pub const ATA_CMD_IDENTIFY_DEVICE: u8 = 0xEC;
+
+#[repr(C)]
+pub struct AtaCommandBlock {
+    pub reserved1: [u8; 4],
+    pub feature: u8,
+    pub feature_exp: u8,
+    pub sector_count: u8,
+    pub sector_count_exp: u8,
+    pub sector_number: u8,
+    pub sector_number_exp: u8,
+    pub cylinder_low: u8,
+    pub cylinder_low_exp: u8,
+    pub cylinder_high: u8,
+    pub cylinder_high_exp: u8,
+    pub device_head: u8,
+    pub command: u8,
+    pub reserved2: [u8; 3],
+}
+
+#[repr(C)]
+pub struct AtaStatusBlock {
+    pub reserved1: [u8; 2],
+    pub error: u8,
+    pub sector_count: u8,
+    pub sector_count_exp: u8,
+    pub sector_number: u8,
+    pub sector_number_exp: u8,
+    pub cylinder_low: u8,
+    pub cylinder_low_exp: u8,
+    pub cylinder_high: u8,
+    pub cylinder_high_exp: u8,
+    pub device_head: u8,
+    pub status: u8,
+    pub reserved2: [u8; 2],
+}
+
+#[repr(u32)]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum AtaPassThruDirection {
+    None = 0,
+    Read = 1,
+    Write = 2,
+}
+
+#[repr(C)]
+pub struct AtaPassThruCommandPacket {
+    pub timeout: u64,
+    pub acb: *mut AtaCommandBlock,
+    pub asb: *mut AtaStatusBlock,
+    pub in_data_buffer: *mut c_void,
+    pub out_data_buffer: *mut c_void,
+    pub in_transfer_length: u32,
+    pub out_transfer_length: u32,
+    pub protocol: u8,
+    pub length: u8,
+}
+
+#[repr(C)]
+#[unsafe_protocol("1d3de7f0-0807-424f-aa69-11a54e19a46f")]
+pub struct AtaPassThru {
+    pub mode: *const c_void,
+    pass_thru: unsafe extern "efiapi" fn(
+        this: *const Self,
+        port: u16,
+        port_multiplier_port: u16,
+        packet: *mut AtaPassThruCommandPacket,
+        event: *mut c_void,
+    ) -> Status,
+    get_next_port: unsafe extern "efiapi" fn(this: *const Self, port: *mut u16) -> Status,
+    get_next_device: unsafe extern "efiapi" fn(this: *const Self, port: u16, port_multiplier_port: *mut u16) -> Status,
+    build_device_path: unsafe extern "efiapi" fn(
+        this: *const Self,
+        port: u16,
+        port_multiplier_port: u16,
+        device_path: *mut *mut c_void,
+    ) -> Status,
+    get_device: unsafe extern "efiapi" fn(
+        this: *const Self,
+        device_path: *const c_void,
+        port: *mut u16,
+        port_multiplier_port: *mut u16,
+    ) -> Status,
+}
+
+impl AtaPassThru {
+    /// Safety: `packet` must describe buffers sized for whatever direction
+    /// the ACB's command byte implies.
+    pub unsafe fn pass_thru(
+        &self,
+        port: u16,
+        port_multiplier_port: u16,
+        packet: &mut AtaPassThruCommandPacket,
+    ) -> Result<(), Status> {
+        match (self.pass_thru)(self, port, port_multiplier_port, packet, core::ptr::null_mut()) {
+            Status::SUCCESS => Ok(()),
+            status => Err(status),
+        }
+    }
+}
@@ -0,0 +1,264 @@
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use crate::{
+    error::{Error, Result},
+    pe_sections,
+};
+
+/// Parsed contents of the `config` file on the ESP, or of the `.config`
+/// section embedded in the greeter's own image (see `from_embedded_image`):
+/// one `key=value` per line, `#` starts a comment, blank lines are ignored.
+#[derive(Debug)]
+pub struct Config {
+    pub image: String,
+    pub args: String,
+    pub prompt: Option<String>,
+    pub retry_prompt: Option<String>,
+    pub sed_locked_msg: Option<String>,
+    pub clear_on_retry: bool,
+    pub log_level: log::LevelFilter,
+    /// Blake3 digest the boot image read off the ESP must match, checked
+    /// before it is handed to `load_image`.
+    pub image_hash: Option<[u8; 32]>,
+    /// HMAC hash backing the PBKDF2 key derivation.
+    pub kdf_hash: KdfHash,
+    pub kdf_iterations: u32,
+    /// 1-based entry the boot menu auto-selects once `boot_menu_timeout_secs`
+    /// elapses with no key pressed.
+    pub default_boot_entry: usize,
+    pub boot_menu_timeout_secs: u32,
+    /// Whether the F2 recovery menu (change credential / PSID revert) is
+    /// reachable at all. Off by default so production boots can't be
+    /// walked into a factory revert by someone at the keyboard.
+    pub allow_recovery: bool,
+    /// Named alternatives to `image`/`args`, e.g. to offer a choice of
+    /// Windows vs. a recovery Linux from the boot menu. Empty unless the
+    /// config file has at least one `boot_entry` line.
+    pub boot_entries: Vec<BootEntry>,
+}
+
+/// One named entry of `boot_entries`, parsed from a `boot_entry =
+/// label|image|args` line (`args` may be omitted).
+#[derive(Debug, Clone)]
+pub struct BootEntry {
+    pub label: String,
+    pub image: String,
+    pub args: String,
+}
+
+/// HMAC hash usable as the PBKDF2 PRF, selectable via `kdf_hash` to match
+/// whatever tooling set up the drive's credential.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KdfHash {
+    Sha1,
+    Sha256,
+    Sha512,
+}
+
+impl core::str::FromStr for KdfHash {
+    type Err = ();
+
+    fn from_str(s: &str) -> core::result::Result<Self, Self::Err> {
+        match s {
+            "sha1" => Ok(Self::Sha1),
+            "sha256" => Ok(Self::Sha256),
+            "sha512" => Ok(Self::Sha512),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            image: String::new(),
+            args: String::new(),
+            prompt: None,
+            retry_prompt: None,
+            sed_locked_msg: None,
+            clear_on_retry: false,
+            log_level: log::LevelFilter::Info,
+            image_hash: None,
+            // matches sedutil-cli's defaults
+            kdf_hash: KdfHash::Sha1,
+            kdf_iterations: 75000,
+            default_boot_entry: 1,
+            boot_menu_timeout_secs: 10,
+            allow_recovery: false,
+            boot_entries: Vec::new(),
+        }
+    }
+}
+
+impl Config {
+    pub fn parse(buf: &[u8]) -> Result<Self> {
+        let text = core::str::from_utf8(buf).map_err(|_| Error::ConfigParse("not valid utf-8"))?;
+
+        let mut config = Self::default();
+        let mut image_set = false;
+
+        for line in text.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (key, value) = line
+                .split_once('=')
+                .ok_or(Error::ConfigParse("expected key=value"))?;
+            let (key, value) = (key.trim(), value.trim());
+
+            match key {
+                "image" => {
+                    config.image = value.to_string();
+                    image_set = true;
+                }
+                "args" => config.args = value.to_string(),
+                "prompt" => config.prompt = Some(value.to_string()),
+                "retry_prompt" => config.retry_prompt = Some(value.to_string()),
+                "sed_locked_msg" => config.sed_locked_msg = Some(value.to_string()),
+                "clear_on_retry" => config.clear_on_retry = value == "true",
+                "image_hash" => config.image_hash = Some(parse_hex_digest(value)?),
+                "kdf_hash" => {
+                    config.kdf_hash = value.parse().map_err(|_| Error::ConfigParse("unknown kdf_hash"))?
+                }
+                "kdf_iterations" => {
+                    config.kdf_iterations = value
+                        .parse()
+                        .map_err(|_| Error::ConfigParse("kdf_iterations must be a number"))?
+                }
+                "default_boot_entry" => {
+                    config.default_boot_entry = value
+                        .parse()
+                        .map_err(|_| Error::ConfigParse("default_boot_entry must be a number"))?
+                }
+                "boot_menu_timeout_secs" => {
+                    config.boot_menu_timeout_secs = value
+                        .parse()
+                        .map_err(|_| Error::ConfigParse("boot_menu_timeout_secs must be a number"))?
+                }
+                "allow_recovery" => config.allow_recovery = value == "true",
+                "boot_entry" => config.boot_entries.push(parse_boot_entry(value)?),
+                "log_level" => {
+                    config.log_level = value
+                        .parse()
+                        .map_err(|_| Error::ConfigParse("unknown log_level"))?
+                }
+                _ => return Err(Error::ConfigParse("unknown key")),
+            }
+        }
+
+        if !image_set {
+            return Err(Error::ConfigParse("missing `image`"));
+        }
+
+        Ok(config)
+    }
+
+    /// Tries to build a `Config` from the named sections of the greeter's
+    /// own loaded PE image, returning `Ok(None)` when no `.config` section
+    /// is present so the caller can fall back to the plaintext file.
+    pub fn from_embedded_image(image: &[u8]) -> Result<Option<Self>> {
+        let sections = pe_sections::sections(image)?;
+
+        let config_bytes = match pe_sections::find(&sections, ".config") {
+            Some(bytes) => bytes,
+            None => return Ok(None),
+        };
+        let mut config = Self::parse(config_bytes)?;
+
+        if let Some(prompt) = pe_sections::find(&sections, ".prompt") {
+            let prompt = core::str::from_utf8(prompt).map_err(|_| Error::ConfigParse("`.prompt` is not valid utf-8"))?;
+            config.prompt = Some(prompt.to_string());
+        }
+
+        if let Some(hash) = pe_sections::find(&sections, ".imghash") {
+            config.image_hash = Some(
+                hash.try_into()
+                    .map_err(|_| Error::ConfigParse("`.imghash` must be exactly 32 bytes"))?,
+            );
+        }
+
+        Ok(Some(config))
+    }
+}
+
+/// Parses a `label|image|args` boot entry line; `args` may be omitted
+/// (`label|image`).
+fn parse_boot_entry(value: &str) -> Result<BootEntry> {
+    let mut parts = value.splitn(3, '|');
+    let label = parts.next().ok_or(Error::ConfigParse("boot_entry missing label"))?;
+    let image = parts
+        .next()
+        .ok_or(Error::ConfigParse("boot_entry missing image"))?;
+    let args = parts.next().unwrap_or("");
+
+    Ok(BootEntry {
+        label: label.to_string(),
+        image: image.to_string(),
+        args: args.to_string(),
+    })
+}
+
+/// Decodes a 64-char lowercase/uppercase hex string into a 32-byte digest,
+/// as produced by `blake3sum` or `b3sum`.
+fn parse_hex_digest(value: &str) -> Result<[u8; 32]> {
+    if value.len() != 64 {
+        return Err(Error::ConfigParse("image_hash must be 64 hex chars"));
+    }
+
+    let mut digest = [0u8; 32];
+    for (byte, chunk) in digest.iter_mut().zip(value.as_bytes().chunks_exact(2)) {
+        let hi = (chunk[0] as char)
+            .to_digit(16)
+            .ok_or(Error::ConfigParse("image_hash is not valid hex"))?;
+        let lo = (chunk[1] as char)
+            .to_digit(16)
+            .ok_or(Error::ConfigParse("image_hash is not valid hex"))?;
+        *byte = ((hi << 4) | lo) as u8;
+    }
+    Ok(digest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_hex_digest_decodes_mixed_case() {
+        let hex = "0123456789abcdefABCDEF0123456789abcdefABCDEF0123456789abcdefAB01";
+        let digest = parse_hex_digest(hex).unwrap();
+        assert_eq!(digest[0], 0x01);
+        assert_eq!(digest[1], 0x23);
+        assert_eq!(digest[2], 0x45);
+        assert_eq!(digest[31], 0x01);
+    }
+
+    #[test]
+    fn parse_hex_digest_rejects_wrong_length() {
+        assert!(parse_hex_digest("abcd").is_err());
+    }
+
+    #[test]
+    fn parse_hex_digest_rejects_non_hex() {
+        let bad = "zz23456789abcdefABCDEF0123456789abcdefABCDEF0123456789abcdefAB01";
+        assert!(parse_hex_digest(bad).is_err());
+    }
+
+    #[test]
+    fn parse_boot_entry_splits_label_image_args() {
+        let entry = parse_boot_entry("Linux|\\EFI\\ubuntu\\grubx64.efi|quiet splash").unwrap();
+        assert_eq!(entry.label, "Linux");
+        assert_eq!(entry.image, "\\EFI\\ubuntu\\grubx64.efi");
+        assert_eq!(entry.args, "quiet splash");
+    }
+
+    #[test]
+    fn parse_boot_entry_defaults_args_when_omitted() {
+        let entry = parse_boot_entry("Windows|\\EFI\\Microsoft\\Boot\\bootmgfw.efi").unwrap();
+        assert_eq!(entry.args, "");
+    }
+}
@@ -0,0 +1,48 @@
+use alloc::string::String;
+
+use crate::opal::StatusCode;
+
+pub type Result<T = ()> = core::result::Result<T, Error>;
+
+#[derive(Debug)]
+pub enum Error {
+    Uefi(uefi::Status),
+    Opal(OpalError),
+    ConfigMissing,
+    ConfigParse(&'static str),
+    ConfigArgsBadUtf16,
+    NoSecureDevices,
+    NoBootPartitions,
+    ImageNotFound(String),
+    ImageNotPeCoff,
+    ImageHashMismatch,
+}
+
+#[derive(Debug)]
+pub enum OpalError {
+    Status(StatusCode),
+    Malformed(&'static str),
+}
+
+/// Lets a `uefi::Result<T, _>` be folded into our own [`Error`] with `?` at
+/// the call site, e.g. `st.boot_services().locate_handle(..).fix(info!())?`.
+/// Generic over the error data (some UEFI calls, like `wait_for_event`,
+/// carry extra payload on failure we don't care about). The `info!()`
+/// argument is unused (kept for parity with the crate's logging macros):
+/// it expands to `()` and carries no call-site information.
+pub trait ResultFixupExt<T> {
+    fn fix(self, _loc: ()) -> Result<T>;
+}
+
+impl<T, E: core::fmt::Debug> ResultFixupExt<T> for uefi::Result<T, E> {
+    fn fix(self, _loc: ()) -> Result<T> {
+        self.map_err(|e| Error::Uefi(e.status()))
+    }
+}
+
+#[macro_export]
+macro_rules! info {
+    () => {
+        ()
+    };
+}
@@ -1,5 +1,5 @@
-#![no_std]
-#![no_main]
+#![cfg_attr(not(test), no_std)]
+#![cfg_attr(not(test), no_main)]
 #![allow(clippy::missing_safety_doc)]
 
 #[macro_use]
@@ -7,7 +7,10 @@ extern crate alloc;
 // make sure to link this
 extern crate rlibc;
 
-use alloc::{string::String, vec::Vec};
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
 use core::{convert::TryFrom, fmt::Write, ops::DerefMut, time::Duration};
 use uefi::table::boot::LoadImageSource;
 
@@ -19,7 +22,7 @@ use uefi::{
         loaded_image::LoadedImage,
         media::{
             block::BlockIO,
-            file::{File, FileAttribute, FileInfo, FileMode, FileType},
+            file::{File, FileAttribute, FileInfo, FileMode, FileSystemInfo, FileType},
             fs::SimpleFileSystem,
             partition::{GptPartitionType, PartitionInfo},
         },
@@ -29,7 +32,9 @@ use uefi::{
 };
 
 use crate::{
-    config::Config,
+    ata_device::AtaDevice,
+    ata_passthru::AtaPassThru,
+    config::{Config, KdfHash},
     error::{Error, OpalError, Result, ResultFixupExt},
     nvme_device::NvmeDevice,
     nvme_passthru::*,
@@ -38,11 +43,14 @@ use crate::{
     util::sleep,
 };
 
+pub mod ata_device;
+pub mod ata_passthru;
 pub mod config;
 pub mod error;
 pub mod nvme_device;
 pub mod nvme_passthru;
 pub mod opal;
+pub mod pe_sections;
 pub mod secure_device;
 pub mod util;
 
@@ -59,7 +67,7 @@ fn main(image_handle: Handle, mut st: SystemTable<Boot>) -> Status {
         sleep(Duration::from_secs(10));
     }
     st.runtime_services()
-        .reset(ResetType::SHUTDOWN, Status::SUCCESS, None)
+        .reset(ResetType::Shutdown, Status::SUCCESS, None)
 }
 
 fn run(image_handle: Handle, st: &mut SystemTable<Boot>) -> Result {
@@ -67,31 +75,45 @@ fn run(image_handle: Handle, st: &mut SystemTable<Boot>) -> Result {
 
     let config = load_config(image_handle, st)?;
 
-    let devices = find_secure_devices(st).fix(info!())?;
+    let devices = find_secure_devices(st)?;
 
     for mut device in devices {
-        if device.recv_locked().fix(info!())? {
+        if device.recv_locked()? {
             // session mutably borrows the device
             {
                 let mut prompt = config.prompt.as_deref().unwrap_or("password: ");
-                let mut session = loop {
-                    let password = read_password(st, prompt)?;
+                let session = loop {
+                    let password = match read_password(st, prompt)? {
+                        PasswordPrompt::Entered(password) => password,
+                        PasswordPrompt::Recovery if config.allow_recovery => {
+                            run_recovery(st, &mut device, &config)?;
+                            // A successful PSID revert resets the Locking SP
+                            // to factory defaults, so the drive may no
+                            // longer be locked at all; re-check rather than
+                            // keep prompting for a credential that can
+                            // never match again.
+                            if !device.recv_locked()? {
+                                break None;
+                            }
+                            continue;
+                        }
+                        PasswordPrompt::Recovery => continue,
+                    };
 
                     let mut hash = vec![0; 32];
 
-                    // as in sedutil-cli, maybe will change
-                    pbkdf2::pbkdf2::<hmac::Hmac<sha1::Sha1>>(
+                    derive_key(
+                        config.kdf_hash,
                         password.as_bytes(),
                         device.proto().serial_num(),
-                        75000,
+                        config.kdf_iterations,
                         &mut hash,
-                    )
-                    .unwrap();
+                    );
 
                     if let Some(s) =
                         pretty_session(st, &mut device, &*hash, config.sed_locked_msg.as_deref())?
                     {
-                        break s;
+                        break Some(s);
                     }
 
                     if config.clear_on_retry {
@@ -104,8 +126,10 @@ fn run(image_handle: Handle, st: &mut SystemTable<Boot>) -> Result {
                         .unwrap_or("bad password, retry: ");
                 };
 
-                session.set_mbr_done(true)?;
-                session.set_locking_range(0, LockingState::ReadWrite)?;
+                if let Some(mut session) = session {
+                    session.set_mbr_done(true)?;
+                    session.set_locking_range(0, LockingState::ReadWrite)?;
+                }
             }
 
             // reconnect the controller to see
@@ -114,7 +138,7 @@ fn run(image_handle: Handle, st: &mut SystemTable<Boot>) -> Result {
         }
     }
 
-    let handle = find_boot_partition(st)?;
+    let (handle, image_path, args_str) = find_boot_partition(&config, st)?;
     let agent = st.boot_services().image_handle();
 
     let dp = unsafe {
@@ -129,16 +153,23 @@ fn run(image_handle: Handle, st: &mut SystemTable<Boot>) -> Result {
     }
     .fix(info!())?;
 
-    let image = CString16::try_from(config.image.as_str()).or(Err(Error::ConfigArgsBadUtf16))?;
+    let image = CString16::try_from(image_path.as_str()).or(Err(Error::ConfigArgsBadUtf16))?;
 
     let buf = read_file(st, handle, &image)
         .fix(info!())?
-        .ok_or(Error::ImageNotFound(config.image))?;
+        .ok_or(Error::ImageNotFound(image_path))?;
 
     if buf.get(0..2) != Some(&[0x4d, 0x5a]) {
         return Err(Error::ImageNotPeCoff);
     }
 
+    if let Some(expected) = config.image_hash {
+        let actual = blake3::hash(&buf);
+        if !constant_time_eq(actual.as_bytes(), &expected) {
+            return Err(Error::ImageHashMismatch);
+        }
+    }
+
     let loaded_image_handle = st
         .boot_services()
         .load_image(
@@ -162,7 +193,7 @@ fn run(image_handle: Handle, st: &mut SystemTable<Boot>) -> Result {
     }
     .fix(info!())?;
 
-    let args = CString16::try_from(&*config.args).or(Err(Error::ConfigArgsBadUtf16))?;
+    let args = CString16::try_from(args_str.as_str()).or(Err(Error::ConfigArgsBadUtf16))?;
     unsafe { loaded_image.set_load_options(args.as_ptr() as *const u8, args.num_bytes() as _) };
 
     st.boot_services()
@@ -172,6 +203,54 @@ fn run(image_handle: Handle, st: &mut SystemTable<Boot>) -> Result {
     Ok(())
 }
 
+/// Compares two equal-length buffers without branching on the data, so a
+/// mismatching boot image can't be used as a timing oracle for the pinned
+/// hash.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Runs PBKDF2 over whichever HMAC `kdf_hash` selects, so a drive set up
+/// with other tooling (or a different `kdf_iterations`) can still be
+/// unlocked.
+fn derive_key(kdf_hash: KdfHash, password: &[u8], salt: &[u8], iterations: u32, out: &mut [u8]) {
+    match kdf_hash {
+        KdfHash::Sha1 => pbkdf2::pbkdf2::<hmac::Hmac<sha1::Sha1>>(password, salt, iterations, out),
+        KdfHash::Sha256 => pbkdf2::pbkdf2::<hmac::Hmac<sha2::Sha256>>(password, salt, iterations, out),
+        KdfHash::Sha512 => pbkdf2::pbkdf2::<hmac::Hmac<sha2::Sha512>>(password, salt, iterations, out),
+    }
+    .unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn constant_time_eq_compares_matching_and_mismatching_buffers() {
+        assert!(constant_time_eq(b"abcd", b"abcd"));
+        assert!(!constant_time_eq(b"abcd", b"abce"));
+        assert!(!constant_time_eq(b"abc", b"abcd"));
+    }
+
+    #[test]
+    fn derive_key_dispatches_to_the_configured_hash() {
+        let mut sha1_out = [0u8; 32];
+        derive_key(KdfHash::Sha1, b"password", b"salt", 10, &mut sha1_out);
+
+        let mut sha256_out = [0u8; 32];
+        derive_key(KdfHash::Sha256, b"password", b"salt", 10, &mut sha256_out);
+
+        // different hashes over the same input must not collide, and the
+        // dispatch must be deterministic given the same inputs
+        assert_ne!(sha1_out, sha256_out);
+
+        let mut sha1_again = [0u8; 32];
+        derive_key(KdfHash::Sha1, b"password", b"salt", 10, &mut sha1_again);
+        assert_eq!(sha1_out, sha1_again);
+    }
+}
+
 fn config_stdout(st: &mut SystemTable<Boot>) -> uefi::Result {
     st.stdout().reset(false)?;
 
@@ -196,26 +275,35 @@ fn load_config(image_handle: Handle, st: &mut SystemTable<Boot>) -> Result<Confi
     }
     .fix(info!())?;
 
-    let device_path = unsafe {
-        st.boot_services().open_protocol::<DevicePath>(
-            uefi::table::boot::OpenProtocolParams {
-                handle: loaded_image.device(),
-                agent: agent,
-                controller: None,
-            },
-            uefi::table::boot::OpenProtocolAttributes::GetProtocol,
-        )
-    }
-    .fix(info!())?;
+    let (image_base, image_size) = loaded_image.info();
+    let image = unsafe { core::slice::from_raw_parts(image_base as *const u8, image_size as usize) };
+
+    let config = match Config::from_embedded_image(image)? {
+        Some(config) => config,
+        None => {
+            let device_path = unsafe {
+                st.boot_services().open_protocol::<DevicePath>(
+                    uefi::table::boot::OpenProtocolParams {
+                        handle: loaded_image.device(),
+                        agent: agent,
+                        controller: None,
+                    },
+                    uefi::table::boot::OpenProtocolAttributes::GetProtocol,
+                )
+            }
+            .fix(info!())?;
+
+            let device_handle = st
+                .boot_services()
+                .locate_device_path::<SimpleFileSystem>(&mut &*device_path)
+                .fix(info!())?;
+            let buf = read_file(st, device_handle, cstr16!("config"))
+                .fix(info!())?
+                .ok_or(Error::ConfigMissing)?;
+            Config::parse(&buf)?
+        }
+    };
 
-    let device_handle = st
-        .boot_services()
-        .locate_device_path::<SimpleFileSystem>(&mut &*device_path)
-        .fix(info!())?;
-    let buf = read_file(st, device_handle, cstr16!("config"))
-        .fix(info!())?
-        .ok_or(Error::ConfigMissing)?;
-    let config = Config::parse(&buf)?;
     log::set_max_level(config.log_level);
     log::debug!("loaded config = {:#?}", config);
     Ok(config)
@@ -228,7 +316,14 @@ fn write_char(st: &mut SystemTable<Boot>, ch: u16) -> Result {
         .fix(info!())
 }
 
-fn read_password(st: &mut SystemTable<Boot>, prompt: &str) -> Result<String> {
+/// What the user did at a [`read_password`] prompt.
+enum PasswordPrompt {
+    Entered(String),
+    /// F2 was pressed instead of Enter: fall into the recovery menu.
+    Recovery,
+}
+
+fn read_password(st: &mut SystemTable<Boot>, prompt: &str) -> Result<PasswordPrompt> {
     st.stdout().write_str(prompt).unwrap();
 
     let mut wait_for_key = [unsafe { st.stdin().wait_for_key_event().unsafe_clone() }];
@@ -243,7 +338,7 @@ fn read_password(st: &mut SystemTable<Boot>, prompt: &str) -> Result<String> {
             Some(Key::Printable(k)) if [0xD, 0xA].contains(&u16::from(k)) => {
                 write_char(st, 0x0D)?;
                 write_char(st, 0x0A)?;
-                break Ok(data);
+                break Ok(PasswordPrompt::Entered(data));
             }
             Some(Key::Printable(k)) if u16::from(k) == 0x8 => {
                 if data.pop().is_some() {
@@ -254,9 +349,10 @@ fn read_password(st: &mut SystemTable<Boot>, prompt: &str) -> Result<String> {
                 write_char(st, '*' as u16)?;
                 data.push(k.into());
             }
+            Some(Key::Special(ScanCode::FUNCTION_2)) => break Ok(PasswordPrompt::Recovery),
             Some(Key::Special(ScanCode::ESCAPE)) => {
                 st.runtime_services()
-                    .reset(ResetType::SHUTDOWN, Status::SUCCESS, None)
+                    .reset(ResetType::Shutdown, Status::SUCCESS, None)
             }
             _ => {}
         }
@@ -276,8 +372,8 @@ fn pretty_session<'d>(
         Some(challenge),
     ) {
         Ok(session) => Ok(Some(session)),
-        Err(Error::Opal(OpalError::Status(StatusCode::NOT_AUTHORIZED))) => Ok(None),
-        Err(Error::Opal(OpalError::Status(StatusCode::AUTHORITY_LOCKED_OUT))) => {
+        Err(Error::Opal(OpalError::Status(StatusCode::NotAuthorized))) => Ok(None),
+        Err(Error::Opal(OpalError::Status(StatusCode::AuthorityLockedOut))) => {
             st.stdout()
                 .write_str(
                     sed_locked_msg
@@ -286,18 +382,91 @@ fn pretty_session<'d>(
                 .unwrap();
             sleep(Duration::from_secs(10));
             st.runtime_services()
-                .reset(ResetType::COLD, Status::WARN_RESET_REQUIRED, None);
+                .reset(ResetType::Cold, Status::WARN_RESET_REQUIRED, None);
         }
         e => e.map(Some),
     }
 }
 
-fn find_secure_devices(st: &mut SystemTable<Boot>) -> uefi::Result<Vec<SecureDevice>> {
+/// Entered from the password prompt via F2 (see `PasswordPrompt::Recovery`).
+/// Gated on `config.allow_recovery` by the caller.
+fn run_recovery(st: &mut SystemTable<Boot>, device: &mut SecureDevice, config: &Config) -> Result {
+    st.stdout()
+        .write_str("\r\nRecovery: 1) change password  2) PSID revert  (any other key cancels)\r\n")
+        .unwrap();
+
+    let mut wait_for_key = [unsafe { st.stdin().wait_for_key_event().unsafe_clone() }];
+    st.boot_services().wait_for_event(&mut wait_for_key).fix(info!())?;
+
+    match st.stdin().read_key().fix(info!())? {
+        Some(Key::Printable(k)) if u16::from(k) == '1' as u16 => recovery_change_password(st, device, config),
+        Some(Key::Printable(k)) if u16::from(k) == '2' as u16 => recovery_psid_revert(st, device),
+        _ => Ok(()),
+    }
+}
+
+fn recovery_change_password(st: &mut SystemTable<Boot>, device: &mut SecureDevice, config: &Config) -> Result {
+    let current = match read_password(st, "current admin password: ")? {
+        PasswordPrompt::Entered(password) => password,
+        PasswordPrompt::Recovery => return Ok(()),
+    };
+    let new_password = match read_password(st, "new password: ")? {
+        PasswordPrompt::Entered(password) => password,
+        PasswordPrompt::Recovery => return Ok(()),
+    };
+
+    let mut current_hash = vec![0; 32];
+    derive_key(
+        config.kdf_hash,
+        current.as_bytes(),
+        device.proto().serial_num(),
+        config.kdf_iterations,
+        &mut current_hash,
+    );
+
+    let mut new_hash = vec![0; 32];
+    derive_key(
+        config.kdf_hash,
+        new_password.as_bytes(),
+        device.proto().serial_num(),
+        config.kdf_iterations,
+        &mut new_hash,
+    );
+
+    match OpalSession::start(device, uid::OPAL_LOCKINGSP, uid::OPAL_ADMIN1, Some(&current_hash))
+        .and_then(|mut session| session.set_new_credential(uid::OPAL_C_PIN_ADMIN1, &new_hash))
+    {
+        Err(Error::Opal(OpalError::Status(_))) => {
+            st.stdout().write_str("\r\nrecovery failed: bad admin password\r\n").unwrap();
+            Ok(())
+        }
+        result => result,
+    }
+}
+
+fn recovery_psid_revert(st: &mut SystemTable<Boot>, device: &mut SecureDevice) -> Result {
+    let psid = match read_password(st, "PSID (printed on the drive label): ")? {
+        PasswordPrompt::Entered(password) => password,
+        PasswordPrompt::Recovery => return Ok(()),
+    };
+
+    match OpalSession::start(device, uid::OPAL_ADMINSP, uid::OPAL_PSID, Some(psid.as_bytes()))
+        .and_then(|mut session| session.revert(uid::OPAL_ADMINSP))
+    {
+        Err(Error::Opal(OpalError::Status(_))) => {
+            st.stdout().write_str("\r\nrecovery failed: bad PSID\r\n").unwrap();
+            Ok(())
+        }
+        result => result,
+    }
+}
+
+fn find_secure_devices(st: &mut SystemTable<Boot>) -> Result<Vec<SecureDevice>> {
     let mut result = Vec::new();
 
     let agent = st.boot_services().image_handle();
 
-    for handle in st.boot_services().find_handles::<BlockIO>()? {
+    for handle in st.boot_services().find_handles::<BlockIO>().fix(info!())? {
         let blockio = unsafe {
             st.boot_services().open_protocol::<BlockIO>(
                 uefi::table::boot::OpenProtocolParams {
@@ -307,7 +476,8 @@ fn find_secure_devices(st: &mut SystemTable<Boot>) -> uefi::Result<Vec<SecureDev
                 },
                 uefi::table::boot::OpenProtocolAttributes::GetProtocol,
             )
-        }?;
+        }
+        .fix(info!())?;
 
         if blockio.media().is_logical_partition() {
             continue;
@@ -322,7 +492,8 @@ fn find_secure_devices(st: &mut SystemTable<Boot>) -> uefi::Result<Vec<SecureDev
                 },
                 uefi::table::boot::OpenProtocolAttributes::GetProtocol,
             )
-        }?;
+        }
+        .fix(info!())?;
 
         if let Ok(nvme) = st
             .boot_services()
@@ -337,35 +508,74 @@ fn find_secure_devices(st: &mut SystemTable<Boot>) -> uefi::Result<Vec<SecureDev
                     },
                     uefi::table::boot::OpenProtocolAttributes::GetProtocol,
                 )
-            }?;
+            }
+            .fix(info!())?;
 
-            let nvme = nvme.deref_mut();
+            // The protocol stays open (and valid) for as long as boot
+            // services do, which outlives `SecureDevice`; keep the raw
+            // pointer and forget the scoped guard instead of tying
+            // `SecureDevice` to this function's borrow of `st`.
+            let nvme_ptr: *mut NvmExpressPassthru = nvme.deref_mut();
+            core::mem::forget(nvme);
 
-            result.push(SecureDevice::new(handle, NvmeDevice::new(nvme)?)?)
+            result.push(SecureDevice::new(handle, unsafe { NvmeDevice::new(nvme_ptr)? })?);
+            continue;
         }
 
-        // todo something like that:
-        //
-        // if let Ok(ata) = st
-        //     .boot_services()
-        //     .locate_device_path::<AtaExpressPassthru>(device_path)
-        //     .log_warning()
-        // {
-        //     let ata = st
-        //         .boot_services()
-        //         .handle_protocol::<AtaExpressPassthru>(ata)?
-        //         .log();
-        //
-        //     result.push(SecureDevice::new(handle, AtaDevice::new(ata.get())?.log())?.log())
-        // }
-        //
-        // ..etc
+        if let Ok(ata) = st
+            .boot_services()
+            .locate_device_path::<AtaPassThru>(&mut &*device_path)
+        {
+            let mut ata = unsafe {
+                st.boot_services().open_protocol::<AtaPassThru>(
+                    uefi::table::boot::OpenProtocolParams {
+                        handle: ata,
+                        agent: agent,
+                        controller: None,
+                    },
+                    uefi::table::boot::OpenProtocolAttributes::GetProtocol,
+                )
+            }
+            .fix(info!())?;
+
+            let ata_ptr: *mut AtaPassThru = ata.deref_mut();
+            core::mem::forget(ata);
+
+            // like the NVMe branch above, which always passes through on
+            // namespace 0: drive the first (and in practice only) port /
+            // port-multiplier-port this controller handle exposes.
+            result.push(SecureDevice::new(handle, unsafe { AtaDevice::new(ata_ptr, 0, 0)? })?);
+        }
     }
     Ok(result.into())
 }
 
-fn find_boot_partition(st: &mut SystemTable<Boot>) -> Result<Handle> {
-    let mut res = None;
+struct BootCandidate {
+    handle: Handle,
+    label: String,
+    image: String,
+    args: String,
+}
+
+/// The `image`/`args` a candidate ESP would boot with: either the single
+/// top-level `image`/`args`, or one of `config.boot_entries` if the config
+/// names any.
+fn configured_images(config: &Config) -> Vec<(&str, &str, &str)> {
+    if config.boot_entries.is_empty() {
+        vec![("", config.image.as_str(), config.args.as_str())]
+    } else {
+        config
+            .boot_entries
+            .iter()
+            .map(|e| (e.label.as_str(), e.image.as_str(), e.args.as_str()))
+            .collect()
+    }
+}
+
+fn find_boot_partition(config: &Config, st: &mut SystemTable<Boot>) -> Result<(Handle, String, String)> {
+    let images = configured_images(config);
+
+    let mut esp_handles = Vec::new();
     for handle in st
         .boot_services()
         .find_handles::<PartitionInfo>()
@@ -374,7 +584,7 @@ fn find_boot_partition(st: &mut SystemTable<Boot>) -> Result<Handle> {
         let pi = unsafe {
             st.boot_services().open_protocol::<PartitionInfo>(
                 uefi::table::boot::OpenProtocolParams {
-                    handle: handle,
+                    handle,
                     agent: st.boot_services().image_handle(),
                     controller: None,
                 },
@@ -383,16 +593,151 @@ fn find_boot_partition(st: &mut SystemTable<Boot>) -> Result<Handle> {
         }
         .fix(info!())?;
 
-        match pi.gpt_partition_entry() {
-            Some(gpt) if { gpt.partition_type_guid } == GptPartitionType::EFI_SYSTEM_PARTITION => {
-                if res.replace(handle).is_some() {
-                    return Err(Error::MultipleBootPartitions);
+        if matches!(pi.gpt_partition_entry(), Some(gpt) if { gpt.partition_type_guid } == GptPartitionType::EFI_SYSTEM_PARTITION) {
+            esp_handles.push(handle);
+        }
+    }
+    // Only ESPs should count towards "multiple ESPs" — find_handles above
+    // sees every partition on the system (root, swap, data, ...).
+    let multiple_esps = esp_handles.len() > 1;
+
+    let mut candidates = Vec::new();
+    for handle in esp_handles {
+        let esp_label = partition_label(st, handle).unwrap_or_else(|| format!("ESP {}", candidates.len() + 1));
+
+        for (entry_label, image, args) in &images {
+            let image_path = CString16::try_from(*image).or(Err(Error::ConfigArgsBadUtf16))?;
+            // skip image/ESP pairs that don't even have the image
+            // present, no point listing a candidate that can't boot
+            if read_file(st, handle, &image_path).fix(info!())?.is_none() {
+                continue;
+            }
+
+            let label = match (entry_label.is_empty(), multiple_esps) {
+                (true, _) => esp_label.clone(),
+                (false, false) => entry_label.to_string(),
+                (false, true) => format!("{entry_label} ({esp_label})"),
+            };
+            candidates.push(BootCandidate {
+                handle,
+                label,
+                image: image.to_string(),
+                args: args.to_string(),
+            });
+        }
+    }
+
+    match candidates.len() {
+        0 => Err(Error::NoBootPartitions),
+        1 => {
+            let c = candidates.remove(0);
+            Ok((c.handle, c.image, c.args))
+        }
+        _ => select_boot_partition(st, config, candidates),
+    }
+}
+
+fn partition_label(st: &SystemTable<Boot>, handle: Handle) -> Option<String> {
+    let mut sfs = unsafe {
+        st.boot_services().open_protocol::<SimpleFileSystem>(
+            uefi::table::boot::OpenProtocolParams {
+                handle,
+                agent: st.boot_services().image_handle(),
+                controller: None,
+            },
+            uefi::table::boot::OpenProtocolAttributes::GetProtocol,
+        )
+    }
+    .ok()?;
+    let info = sfs.open_volume().ok()?.get_boxed_info::<FileSystemInfo>().ok()?;
+    let label = info.volume_label().to_string();
+    (!label.is_empty()).then_some(label)
+}
+
+/// Renders a numbered menu of `candidates` and lets the user pick one with
+/// the arrow keys, Enter, or a number key, reusing the key handling
+/// `read_password` already does. Falls back to `config.default_boot_entry`
+/// if nothing is pressed within `config.boot_menu_timeout_secs`.
+fn select_boot_partition(
+    st: &mut SystemTable<Boot>,
+    config: &Config,
+    mut candidates: Vec<BootCandidate>,
+) -> Result<(Handle, String, String)> {
+    let mut selected = config.default_boot_entry.saturating_sub(1).min(candidates.len() - 1);
+
+    draw_boot_menu(st, config, &candidates, selected)?;
+
+    let deadline_polls = config.boot_menu_timeout_secs.max(1) * 10;
+    for _ in 0..deadline_polls {
+        if let Some(key) = st.stdin().read_key().fix(info!())? {
+            let moved = match key {
+                Key::Special(ScanCode::UP) => {
+                    selected = selected.checked_sub(1).unwrap_or(candidates.len() - 1);
+                    true
+                }
+                Key::Special(ScanCode::DOWN) => {
+                    selected = (selected + 1) % candidates.len();
+                    true
+                }
+                Key::Printable(k) if [0xD, 0xA].contains(&u16::from(k)) => {
+                    let c = candidates.remove(selected);
+                    return Ok((c.handle, c.image, c.args));
                 }
+                Key::Printable(k) => {
+                    let digit = u16::from(k);
+                    if (0x31..=0x39).contains(&digit) {
+                        let idx = (digit - 0x31) as usize;
+                        if idx < candidates.len() {
+                            let c = candidates.remove(idx);
+                            return Ok((c.handle, c.image, c.args));
+                        }
+                    }
+                    false
+                }
+                _ => false,
+            };
+            if moved {
+                draw_boot_menu(st, config, &candidates, selected)?;
             }
-            _ => {}
         }
+        sleep(Duration::from_millis(100));
+    }
+
+    let c = candidates.remove(selected);
+    Ok((c.handle, c.image, c.args))
+}
+
+fn draw_boot_menu(
+    st: &mut SystemTable<Boot>,
+    config: &Config,
+    candidates: &[BootCandidate],
+    selected: usize,
+) -> Result {
+    st.stdout().clear().fix(info!())?;
+    st.stdout().write_str("\r\nMultiple boot entries found:\r\n\r\n").unwrap();
+    for (i, candidate) in candidates.iter().enumerate() {
+        write_menu_entry(st, i, &candidate.label, i == selected)?;
     }
-    res.ok_or(Error::NoBootPartitions)
+    write!(
+        st.stdout(),
+        "\r\nUse up/down and Enter to choose, auto-booting entry {} in {}s..\r\n",
+        selected + 1,
+        config.boot_menu_timeout_secs
+    )
+    .unwrap();
+    Ok(())
+}
+
+fn write_menu_entry(st: &mut SystemTable<Boot>, index: usize, label: &str, selected: bool) -> Result {
+    write!(
+        st.stdout(),
+        "{} {}) {}\r\n",
+        if selected { '>' } else { ' ' },
+        index + 1,
+        label
+    )
+    .unwrap();
+    Ok(())
 }
 
 fn read_file(
@@ -0,0 +1,148 @@
+use alloc::vec::Vec;
+use core::ffi::c_void;
+
+use crate::{
+    error::{Error, OpalError, Result},
+    nvme_passthru::{
+        NvmExpressCommand, NvmExpressCompletion, NvmExpressPassThruCommandPacket, NvmExpressPassthru,
+        NVME_ADMIN_CMD_SECURITY_RECEIVE, NVME_ADMIN_CMD_SECURITY_SEND,
+    },
+    opal::StatusCode,
+    secure_device::SecureDeviceIo,
+};
+
+/// TCG Opal over NVMe, via the admin Security Send/Receive commands
+/// (NVMe base spec 1.4, figure 142/143).
+///
+/// `proto` is a raw pointer rather than a borrow because the
+/// `NvmExpressPassthru` it points at is opened once in
+/// `find_secure_devices` and kept open for the rest of boot services, well
+/// past the scope that looked it up.
+pub struct NvmeDevice {
+    proto: *mut NvmExpressPassthru,
+    serial_num: Vec<u8>,
+}
+
+impl NvmeDevice {
+    /// Safety: `proto` must point to a live `NvmExpressPassthru` that
+    /// outlives this `NvmeDevice`.
+    pub unsafe fn new(proto: *mut NvmExpressPassthru) -> Result<Self> {
+        let serial_num = identify_serial_num(&mut *proto)?;
+        Ok(Self { proto, serial_num })
+    }
+
+    fn proto(&mut self) -> &mut NvmExpressPassthru {
+        unsafe { &mut *self.proto }
+    }
+
+    fn security_command(
+        &mut self,
+        opcode: u8,
+        protocol_id: u8,
+        com_id: u16,
+        buf: &mut [u8],
+        is_send: bool,
+    ) -> Result<usize> {
+        let mut cmd = NvmExpressCommand {
+            cdw0: opcode as u32,
+            flags: 0,
+            nsid: 0,
+            cdw2: 0,
+            cdw3: 0,
+            cdw10: ((protocol_id as u32) << 24) | ((com_id as u32) << 8),
+            cdw11: buf.len() as u32,
+            cdw12: 0,
+            cdw13: 0,
+            cdw14: 0,
+            cdw15: 0,
+        };
+        let mut completion = NvmExpressCompletion {
+            dw0: 0,
+            dw1: 0,
+            dw2: 0,
+            dw3: 0,
+        };
+
+        let mut packet = NvmExpressPassThruCommandPacket {
+            command_timeout: 0,
+            transfer_buffer: buf.as_mut_ptr() as *mut c_void,
+            transfer_length: if is_send { 0 } else { buf.len() as u32 },
+            metadata_buffer: core::ptr::null_mut(),
+            metadata_length: 0,
+            queue_type: 0,
+            nvme_cmd: &mut cmd,
+            nvme_completion: &mut completion,
+        };
+        if is_send {
+            packet.metadata_buffer = buf.as_mut_ptr() as *mut c_void;
+            packet.metadata_length = buf.len() as u32;
+            packet.transfer_buffer = buf.as_mut_ptr() as *mut c_void;
+            packet.transfer_length = buf.len() as u32;
+        }
+
+        unsafe { self.proto().pass_thru(0, &mut packet) }
+            .map_err(|_| Error::Opal(OpalError::Status(StatusCode::Fail)))?;
+
+        let status = StatusCode::from_u8((completion.dw3 & 0xff) as u8);
+        if status != StatusCode::Success {
+            return Err(Error::Opal(OpalError::Status(status)));
+        }
+        Ok(buf.len())
+    }
+}
+
+impl SecureDeviceIo for NvmeDevice {
+    fn security_send(&mut self, protocol_id: u8, com_id: u16, buf: &[u8]) -> Result {
+        let mut buf = buf.to_vec();
+        self.security_command(NVME_ADMIN_CMD_SECURITY_SEND, protocol_id, com_id, &mut buf, true)
+            .map(|_| ())
+    }
+
+    fn security_recv(&mut self, protocol_id: u8, com_id: u16, buf: &mut [u8]) -> Result<usize> {
+        self.security_command(NVME_ADMIN_CMD_SECURITY_RECEIVE, protocol_id, com_id, buf, false)
+    }
+
+    fn serial_num(&self) -> &[u8] {
+        &self.serial_num
+    }
+}
+
+fn identify_serial_num(proto: &mut NvmExpressPassthru) -> Result<Vec<u8>> {
+    let mut data = vec![0u8; 4096];
+
+    let mut cmd = NvmExpressCommand {
+        cdw0: 0x06, // Identify
+        flags: 0,
+        nsid: 0,
+        cdw2: 0,
+        cdw3: 0,
+        cdw10: 0x01, // CNS = Identify Controller
+        cdw11: 0,
+        cdw12: 0,
+        cdw13: 0,
+        cdw14: 0,
+        cdw15: 0,
+    };
+    let mut completion = NvmExpressCompletion {
+        dw0: 0,
+        dw1: 0,
+        dw2: 0,
+        dw3: 0,
+    };
+    let mut packet = NvmExpressPassThruCommandPacket {
+        command_timeout: 0,
+        transfer_buffer: data.as_mut_ptr() as *mut c_void,
+        transfer_length: data.len() as u32,
+        metadata_buffer: core::ptr::null_mut(),
+        metadata_length: 0,
+        queue_type: 0,
+        nvme_cmd: &mut cmd,
+        nvme_completion: &mut completion,
+    };
+
+    unsafe { proto.pass_thru(0, &mut packet) }.map_err(|_| Error::Opal(OpalError::Status(StatusCode::Fail)))?;
+
+    // Identify Controller: SN is bytes 4..24 of the data structure, space-padded.
+    let sn = data[4..24].to_vec();
+    Ok(sn)
+}
@@ -0,0 +1,79 @@
+//! Minimal binding for `EFI_NVM_EXPRESS_PASS_THRU_PROTOCOL`, just enough of
+//! it to issue the Security Send/Receive admin commands `nvme_device` needs.
+
+use core::ffi::c_void;
+
+use uefi::{proto::unsafe_protocol, Status};
+
+#[repr(C)]
+pub struct NvmExpressCommand {
+    pub cdw0: u32,
+    pub flags: u8,
+    pub nsid: u32,
+    pub cdw2: u32,
+    pub cdw3: u32,
+    pub cdw10: u32,
+    pub cdw11: u32,
+    pub cdw12: u32,
+    pub cdw13: u32,
+    pub cdw14: u32,
+    pub cdw15: u32,
+}
+
+#[repr(C)]
+pub struct NvmExpressCompletion {
+    pub dw0: u32,
+    pub dw1: u32,
+    pub dw2: u32,
+    pub dw3: u32,
+}
+
+#[repr(C)]
+pub struct NvmExpressPassThruCommandPacket {
+    pub command_timeout: u64,
+    pub transfer_buffer: *mut c_void,
+    pub transfer_length: u32,
+    pub metadata_buffer: *mut c_void,
+    pub metadata_length: u32,
+    pub queue_type: u8,
+    pub nvme_cmd: *mut NvmExpressCommand,
+    pub nvme_completion: *mut NvmExpressCompletion,
+}
+
+pub const NVME_ADMIN_CMD_SECURITY_SEND: u8 = 0x81;
+pub const NVME_ADMIN_CMD_SECURITY_RECEIVE: u8 = 0x82;
+
+#[repr(C)]
+#[unsafe_protocol("52c78312-8edc-4233-98f2-1a1aa5e388a5")]
+pub struct NvmExpressPassthru {
+    pub mode: *const c_void,
+    pass_thru: unsafe extern "efiapi" fn(
+        this: *const Self,
+        namespace_id: u32,
+        packet: *mut NvmExpressPassThruCommandPacket,
+        event: *mut c_void,
+    ) -> Status,
+    get_next_namespace: unsafe extern "efiapi" fn(this: *const Self, namespace_id: *mut u32) -> Status,
+    build_device_path:
+        unsafe extern "efiapi" fn(this: *const Self, namespace_id: u32, device_path: *mut *mut c_void) -> Status,
+    get_namespace: unsafe extern "efiapi" fn(
+        this: *const Self,
+        device_path: *const c_void,
+        namespace_id: *mut u32,
+    ) -> Status,
+}
+
+impl NvmExpressPassthru {
+    /// Safety: `packet` must describe a valid, appropriately sized buffer
+    /// for the admin command's data-in/data-out direction.
+    pub unsafe fn pass_thru(
+        &self,
+        namespace_id: u32,
+        packet: &mut NvmExpressPassThruCommandPacket,
+    ) -> Result<(), Status> {
+        match (self.pass_thru)(self, namespace_id, packet, core::ptr::null_mut()) {
+            Status::SUCCESS => Ok(()),
+            status => Err(status),
+        }
+    }
+}
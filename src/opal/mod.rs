@@ -0,0 +1,103 @@
+//! Minimal TCG Opal SSC building blocks shared by the device backends and
+//! [`session`].
+//!
+//! The backends (`nvme_device`, `ata_device`) are only responsible for
+//! getting a ComPacket to and from the drive via SECURITY SEND/RECEIVE (or
+//! the NVMe equivalent); everything about what's *inside* that ComPacket
+//! lives here so both backends agree on the wire format.
+
+pub mod session;
+pub mod uid;
+
+use alloc::vec::Vec;
+
+use crate::error::{Error, OpalError, Result};
+
+/// Security protocol id for TCG Opal, as passed to SECURITY SEND/RECEIVE.
+pub const OPAL_SECURITY_PROTOCOL_ID: u8 = 0x01;
+
+/// ComID used for the single synchronous session this greeter ever opens.
+/// A real client would negotiate this via a Level 0 Discovery SECURITY
+/// RECEIVE first; we hardcode the ComID handed out on most Opal firmware
+/// before any session has been started.
+pub const OPAL_COM_ID: u16 = 0x07fe;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum StatusCode {
+    Success = 0x00,
+    NotAuthorized = 0x01,
+    InvalidParameter = 0x03,
+    Tper = 0x04,
+    AuthorityLockedOut = 0x06,
+    Fail = 0x3f,
+}
+
+impl StatusCode {
+    pub(crate) fn from_u8(value: u8) -> Self {
+        match value {
+            0x00 => Self::Success,
+            0x01 => Self::NotAuthorized,
+            0x03 => Self::InvalidParameter,
+            0x04 => Self::Tper,
+            0x06 => Self::AuthorityLockedOut,
+            _ => Self::Fail,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LockingState {
+    ReadWrite,
+    ReadOnly,
+    LockedOut,
+}
+
+/// Wraps a raw method-call payload in the ComPacket/Packet/SubPacket
+/// envelope the TCG Storage Core spec requires around it.
+pub(crate) fn wrap_com_packet(com_id: u16, payload: &[u8]) -> Vec<u8> {
+    let mut sub_packet = vec![0u8; 12 + payload.len()];
+    sub_packet[11] = payload.len() as u8; // length (low byte is plenty here)
+    sub_packet[12..].copy_from_slice(payload);
+    while sub_packet.len() % 4 != 0 {
+        sub_packet.push(0);
+    }
+
+    let mut packet = vec![0u8; 24];
+    packet[18..20].copy_from_slice(&(sub_packet.len() as u32).to_be_bytes()[2..]);
+    packet.extend_from_slice(&sub_packet);
+    while packet.len() % 4 != 0 {
+        packet.push(0);
+    }
+
+    let mut com_packet = vec![0u8; 20];
+    com_packet[4..6].copy_from_slice(&com_id.to_be_bytes());
+    com_packet[16..20].copy_from_slice(&(packet.len() as u32).to_be_bytes());
+    com_packet.extend_from_slice(&packet);
+    com_packet
+}
+
+/// Strips the ComPacket/Packet/SubPacket envelope back off, returning the
+/// method-call (or method-result) payload it carried.
+pub(crate) fn unwrap_com_packet(buf: &[u8]) -> Result<&[u8]> {
+    if buf.len() < 20 {
+        return Err(Error::Opal(OpalError::Malformed("short ComPacket")));
+    }
+    let packet_len = u32::from_be_bytes(buf[16..20].try_into().unwrap()) as usize;
+    let packet = buf
+        .get(20..20 + packet_len)
+        .ok_or(Error::Opal(OpalError::Malformed("truncated Packet")))?;
+
+    if packet.len() < 24 {
+        return Err(Error::Opal(OpalError::Malformed("short Packet")));
+    }
+    let sub_packet = &packet[24..];
+
+    if sub_packet.len() < 12 {
+        return Err(Error::Opal(OpalError::Malformed("short SubPacket")));
+    }
+    let payload_len = u32::from_be_bytes(sub_packet[8..12].try_into().unwrap()) as usize;
+    sub_packet
+        .get(12..12 + payload_len)
+        .ok_or(Error::Opal(OpalError::Malformed("truncated SubPacket payload")))
+}
@@ -0,0 +1,122 @@
+use alloc::vec::Vec;
+
+use crate::{
+    error::{Error, OpalError, Result},
+    opal::{
+        uid::{self, Uid},
+        unwrap_com_packet, wrap_com_packet, LockingState, StatusCode, OPAL_COM_ID, OPAL_SECURITY_PROTOCOL_ID,
+    },
+    secure_device::{SecureDevice, SecureDeviceIo},
+};
+
+/// A live StartSession against `sp` as `authority`, held open for the
+/// lifetime of the borrow of `device`.
+pub struct OpalSession<'d> {
+    device: &'d mut SecureDevice,
+    host_session_num: u32,
+    tper_session_num: u32,
+}
+
+impl<'d> OpalSession<'d> {
+    pub fn start(
+        device: &'d mut SecureDevice,
+        sp: Uid,
+        authority: Uid,
+        challenge: Option<&[u8]>,
+    ) -> Result<Self> {
+        let host_session_num: u32 = 1;
+
+        let mut payload = Vec::new();
+        payload.extend_from_slice(&sp);
+        payload.extend_from_slice(&host_session_num.to_be_bytes());
+        payload.push(1); // write = true
+        payload.extend_from_slice(&authority);
+        if let Some(challenge) = challenge {
+            payload.extend_from_slice(challenge);
+        }
+
+        let com_packet = wrap_com_packet(OPAL_COM_ID, &payload);
+        device.security_send(OPAL_SECURITY_PROTOCOL_ID, OPAL_COM_ID, &com_packet)?;
+
+        let mut response = vec![0u8; 512];
+        device.security_recv(OPAL_SECURITY_PROTOCOL_ID, OPAL_COM_ID, &mut response)?;
+        let result = unwrap_com_packet(&response)?;
+        let status = StatusCode::from_u8(result.last().copied().unwrap_or(0x3f));
+        if status != StatusCode::Success {
+            return Err(Error::Opal(OpalError::Status(status)));
+        }
+
+        Ok(Self {
+            device,
+            host_session_num,
+            tper_session_num: host_session_num,
+        })
+    }
+
+    pub fn set_mbr_done(&mut self, done: bool) -> Result {
+        self.invoke_set(b"MBRDone", &[done as u8])
+    }
+
+    pub fn set_locking_range(&mut self, range: u8, state: LockingState) -> Result {
+        let (read_locked, write_locked) = match state {
+            LockingState::ReadWrite => (false, false),
+            LockingState::ReadOnly => (false, true),
+            LockingState::LockedOut => (true, true),
+        };
+        let object = uid::locking_range(range);
+        self.invoke_set_on(object, b"ReadLocked", &[read_locked as u8])?;
+        self.invoke_set_on(object, b"WriteLocked", &[write_locked as u8])
+    }
+
+    /// Rotates the drive credential: invokes the C_PIN Set method against
+    /// `credential` (e.g. `uid::OPAL_C_PIN_ADMIN1`) with a freshly
+    /// PBKDF2-derived `new_hash` as the PIN column. Requires a session
+    /// already authenticated as an authority allowed to write that PIN.
+    pub fn set_new_credential(&mut self, credential: Uid, new_hash: &[u8]) -> Result {
+        self.invoke_set_on(credential, b"PIN", new_hash)
+    }
+
+    /// Invokes the Revert method on `sp` (normally `uid::OPAL_ADMINSP`),
+    /// factory-resetting the drive. Only ever meaningful on a session
+    /// authenticated against the SID or PSID authority.
+    pub fn revert(&mut self, sp: Uid) -> Result {
+        let mut payload = Vec::new();
+        payload.extend_from_slice(&self.tper_session_num.to_be_bytes());
+        payload.extend_from_slice(&sp);
+        payload.extend_from_slice(&uid::OPAL_METHOD_REVERT);
+        self.send_method(&payload)
+    }
+
+    /// `set_mbr_done` acts on the global locking range object; route it
+    /// through `invoke_set_on` so it shares the same method-payload
+    /// encoding as every other Set call.
+    fn invoke_set(&mut self, column: &[u8], value: &[u8]) -> Result {
+        self.invoke_set_on(uid::OPAL_LOCKING_RANGE_GLOBAL, column, value)
+    }
+
+    fn invoke_set_on(&mut self, object: Uid, column: &[u8], value: &[u8]) -> Result {
+        let mut payload = Vec::new();
+        payload.extend_from_slice(&self.tper_session_num.to_be_bytes());
+        payload.extend_from_slice(&object);
+        payload.extend_from_slice(&uid::OPAL_METHOD_SET);
+        payload.extend_from_slice(column);
+        payload.extend_from_slice(value);
+        self.send_method(&payload)
+    }
+
+    fn send_method(&mut self, payload: &[u8]) -> Result {
+        let com_packet = wrap_com_packet(OPAL_COM_ID, payload);
+        self.device
+            .security_send(OPAL_SECURITY_PROTOCOL_ID, OPAL_COM_ID, &com_packet)?;
+
+        let mut response = vec![0u8; 512];
+        self.device
+            .security_recv(OPAL_SECURITY_PROTOCOL_ID, OPAL_COM_ID, &mut response)?;
+        let result = unwrap_com_packet(&response)?;
+        let status = StatusCode::from_u8(result.last().copied().unwrap_or(0x3f));
+        if status != StatusCode::Success {
+            return Err(Error::Opal(OpalError::Status(status)));
+        }
+        Ok(())
+    }
+}
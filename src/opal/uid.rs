@@ -0,0 +1,38 @@
+//! Well-known TCG Opal object and method UIDs (8 bytes each).
+//!
+//! These are the fixed UIDs defined by the TCG Storage Opal SSC, used to
+//! address security providers (SPs), authorities and methods when building
+//! Opal method invocations.
+
+pub type Uid = [u8; 8];
+
+pub const OPAL_ADMINSP: Uid = [0x00, 0x00, 0x02, 0x05, 0x00, 0x00, 0x00, 0x01];
+pub const OPAL_LOCKINGSP: Uid = [0x00, 0x00, 0x02, 0x05, 0x00, 0x00, 0x00, 0x02];
+
+pub const OPAL_ADMIN1: Uid = [0x00, 0x00, 0x00, 0x09, 0x00, 0x01, 0x00, 0x01];
+pub const OPAL_SID: Uid = [0x00, 0x00, 0x00, 0x09, 0x00, 0x00, 0x00, 0x06];
+pub const OPAL_PSID: Uid = [0x00, 0x00, 0x00, 0x09, 0x00, 0x01, 0xff, 0x01];
+
+pub const OPAL_C_PIN_ADMIN1: Uid = [0x00, 0x00, 0x00, 0x0b, 0x00, 0x01, 0x00, 0x01];
+pub const OPAL_C_PIN_SID: Uid = [0x00, 0x00, 0x00, 0x0b, 0x00, 0x00, 0x00, 0x01];
+pub const OPAL_C_PIN_PSID: Uid = [0x00, 0x00, 0x00, 0x0b, 0x00, 0x01, 0xff, 0x01];
+
+pub const OPAL_LOCKING_RANGE_GLOBAL: Uid = [0x00, 0x00, 0x08, 0x02, 0x00, 0x00, 0x00, 0x01];
+
+/// UID of the locking range object for `range` (0 = global, N = Locking
+/// Range N). Locking range objects are numbered sequentially starting
+/// right after the global range, which is itself `..00 00 01`.
+pub fn locking_range(range: u8) -> Uid {
+    if range == 0 {
+        OPAL_LOCKING_RANGE_GLOBAL
+    } else {
+        let mut uid = OPAL_LOCKING_RANGE_GLOBAL;
+        uid[7] += range;
+        uid
+    }
+}
+
+pub const OPAL_METHOD_STARTSESSION: Uid = [0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x02];
+pub const OPAL_METHOD_SET: Uid = [0x00, 0x00, 0x00, 0x06, 0x00, 0x00, 0x00, 0x06];
+pub const OPAL_METHOD_GET: Uid = [0x00, 0x00, 0x00, 0x06, 0x00, 0x00, 0x00, 0x16];
+pub const OPAL_METHOD_REVERT: Uid = [0x00, 0x00, 0x00, 0x06, 0x00, 0x00, 0x00, 0x02];
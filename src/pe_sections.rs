@@ -0,0 +1,141 @@
+//! Just enough of a PE32(+) parser to pull named sections back out of the
+//! greeter's own loaded image, so config can travel inside the
+//! Secure-Boot-signed binary instead of a plaintext file on the ESP.
+//!
+//! `sections()` is handed the image as mapped by `LoadedImage::info()`, not
+//! the on-disk file, so sections must be located by `VirtualAddress`
+//! (relative to the image base) rather than `PointerToRawData` (the
+//! on-disk file offset) — the two only coincide by accident of alignment.
+
+use alloc::vec::Vec;
+
+use crate::error::{Error, Result};
+
+pub struct Section<'a> {
+    name: [u8; 8],
+    data: &'a [u8],
+}
+
+impl Section<'_> {
+    fn matches(&self, name: &str) -> bool {
+        let name = name.as_bytes();
+        self.name.len() >= name.len()
+            && &self.name[..name.len()] == name
+            && self.name[name.len()..].iter().all(|&b| b == 0)
+    }
+}
+
+/// Walks the COFF section table of `image` and returns every section found.
+pub fn sections(image: &[u8]) -> Result<Vec<Section<'_>>> {
+    if image.get(0..2) != Some(&[b'M', b'Z']) {
+        return Err(Error::ConfigParse("embedded image is not MZ"));
+    }
+
+    let e_lfanew = read_u32(image, 0x3c)? as usize;
+    if image.get(e_lfanew..e_lfanew + 4) != Some(b"PE\0\0") {
+        return Err(Error::ConfigParse("embedded image has no PE signature"));
+    }
+
+    let coff = e_lfanew + 4;
+    let number_of_sections = read_u16(image, coff + 2)? as usize;
+    let size_of_optional_header = read_u16(image, coff + 16)? as usize;
+    let section_table = coff + 20 + size_of_optional_header;
+
+    let mut result = Vec::with_capacity(number_of_sections);
+    for i in 0..number_of_sections {
+        let entry = section_table + i * 40;
+        let mut name = [0u8; 8];
+        name.copy_from_slice(
+            image
+                .get(entry..entry + 8)
+                .ok_or(Error::ConfigParse("truncated section table"))?,
+        );
+
+        let virtual_size = read_u32(image, entry + 8)? as usize;
+        let virtual_address = read_u32(image, entry + 12)? as usize;
+        let data = image
+            .get(virtual_address..virtual_address + virtual_size)
+            .ok_or(Error::ConfigParse("section data out of bounds"))?;
+
+        result.push(Section { name, data });
+    }
+    Ok(result)
+}
+
+/// Returns the raw contents of the first section named `name`, if any.
+pub fn find<'a>(sections: &[Section<'a>], name: &str) -> Option<&'a [u8]> {
+    sections.iter().find(|s| s.matches(name)).map(|s| s.data)
+}
+
+fn read_u16(image: &[u8], offset: usize) -> Result<u16> {
+    image
+        .get(offset..offset + 2)
+        .and_then(|b| b.try_into().ok())
+        .map(u16::from_le_bytes)
+        .ok_or(Error::ConfigParse("truncated PE header"))
+}
+
+fn read_u32(image: &[u8], offset: usize) -> Result<u32> {
+    image
+        .get(offset..offset + 4)
+        .and_then(|b| b.try_into().ok())
+        .map(u32::from_le_bytes)
+        .ok_or(Error::ConfigParse("truncated PE header"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds a minimal synthetic PE32 image containing a single section
+    /// named `name` whose virtual address range holds `data`, so
+    /// `sections()`/`find()` can be exercised without a real binary.
+    fn synthetic_image(name: &[u8], data: &[u8]) -> Vec<u8> {
+        const E_LFANEW: usize = 0x40;
+        const COFF: usize = E_LFANEW + 4;
+        const SECTION_TABLE: usize = COFF + 20; // SizeOfOptionalHeader = 0
+        const VIRTUAL_ADDRESS: usize = 0x200;
+
+        let mut image = alloc::vec![0u8; VIRTUAL_ADDRESS + data.len()];
+        image[0..2].copy_from_slice(b"MZ");
+        image[0x3c..0x40].copy_from_slice(&(E_LFANEW as u32).to_le_bytes());
+        image[E_LFANEW..E_LFANEW + 4].copy_from_slice(b"PE\0\0");
+        image[COFF + 2..COFF + 4].copy_from_slice(&1u16.to_le_bytes()); // NumberOfSections
+        image[COFF + 16..COFF + 18].copy_from_slice(&0u16.to_le_bytes()); // SizeOfOptionalHeader
+
+        assert!(name.len() <= 8);
+        image[SECTION_TABLE..SECTION_TABLE + name.len()].copy_from_slice(name);
+        image[SECTION_TABLE + 8..SECTION_TABLE + 12].copy_from_slice(&(data.len() as u32).to_le_bytes());
+        image[SECTION_TABLE + 12..SECTION_TABLE + 16].copy_from_slice(&(VIRTUAL_ADDRESS as u32).to_le_bytes());
+
+        image[VIRTUAL_ADDRESS..VIRTUAL_ADDRESS + data.len()].copy_from_slice(data);
+        image
+    }
+
+    #[test]
+    fn sections_finds_a_named_section_by_virtual_address() {
+        let image = synthetic_image(b".config", b"image=x\nargs=y\n");
+        let sections = sections(&image).unwrap();
+        assert_eq!(find(&sections, ".config"), Some(&b"image=x\nargs=y\n"[..]));
+    }
+
+    #[test]
+    fn find_returns_none_for_a_missing_section() {
+        let image = synthetic_image(b".config", b"payload");
+        let sections = sections(&image).unwrap();
+        assert_eq!(find(&sections, ".prompt"), None);
+    }
+
+    #[test]
+    fn find_does_not_match_on_a_name_prefix() {
+        let image = synthetic_image(b".config", b"payload");
+        let sections = sections(&image).unwrap();
+        // ".conf" is a prefix of ".config" but must not match
+        assert_eq!(find(&sections, ".conf"), None);
+    }
+
+    #[test]
+    fn sections_rejects_a_non_mz_image() {
+        assert!(sections(&[0u8; 64]).is_err());
+    }
+}
@@ -0,0 +1,89 @@
+use alloc::boxed::Box;
+use core::time::Duration;
+
+use uefi::{table::boot::BootServices, Handle};
+
+use crate::{
+    error::{Error, OpalError, Result},
+    opal::{self, StatusCode},
+    util::sleep,
+};
+
+/// Whatever gets a TCG Opal ComPacket to and from the drive. `NvmeDevice`
+/// and `AtaDevice` are the two backends that implement it today, one per
+/// transport the firmware exposes a pass-thru protocol for.
+pub trait SecureDeviceIo {
+    fn security_send(&mut self, protocol_id: u8, com_id: u16, buf: &[u8]) -> Result;
+    fn security_recv(&mut self, protocol_id: u8, com_id: u16, buf: &mut [u8]) -> Result<usize>;
+    fn serial_num(&self) -> &[u8];
+}
+
+/// A drive found to speak TCG Opal, wrapping whichever transport backend
+/// `find_secure_devices` located it through.
+pub struct SecureDevice {
+    handle: Handle,
+    io: Box<dyn SecureDeviceIo>,
+}
+
+impl SecureDevice {
+    pub fn new(handle: Handle, io: impl SecureDeviceIo + 'static) -> Result<Self> {
+        Ok(Self {
+            handle,
+            io: Box::new(io),
+        })
+    }
+
+    pub fn proto(&self) -> &dyn SecureDeviceIo {
+        &*self.io
+    }
+
+    /// Whether the drive currently has its global locking range locked for
+    /// read/write, i.e. whether it's worth prompting for a password at all.
+    pub fn recv_locked(&mut self) -> Result<bool> {
+        let mut buf = [0u8; 512];
+        // Level 0 discovery (ComID 0x0001): a 48-byte header followed by
+        // feature descriptors; the "Locked" bit (0x04) sits in the first
+        // data byte of the Locking feature descriptor (feature code 0x0002).
+        if self.io.security_recv(opal::OPAL_SECURITY_PROTOCOL_ID, 0x0001, &mut buf).is_err() {
+            return Ok(true);
+        }
+        let mut offset = 48;
+        while offset + 4 < buf.len() {
+            let feature = u16::from_be_bytes([buf[offset], buf[offset + 1]]);
+            if feature == 0x0002 {
+                return Ok(buf[offset + 4] & 0x04 != 0);
+            }
+            offset += 4 + buf[offset + 3] as usize;
+        }
+        Ok(true)
+    }
+
+    pub fn reconnect_controller(&mut self, st: &mut uefi::table::SystemTable<uefi::table::Boot>) -> uefi::Result {
+        let bs: &BootServices = st.boot_services();
+        bs.disconnect_controller(self.handle, None, None)?;
+        sleep(Duration::from_millis(500));
+        bs.connect_controller(self.handle, None, None, true)
+    }
+}
+
+impl SecureDeviceIo for SecureDevice {
+    fn security_send(&mut self, protocol_id: u8, com_id: u16, buf: &[u8]) -> Result {
+        self.io.security_send(protocol_id, com_id, buf)
+    }
+
+    fn security_recv(&mut self, protocol_id: u8, com_id: u16, buf: &mut [u8]) -> Result<usize> {
+        self.io.security_recv(protocol_id, com_id, buf)
+    }
+
+    fn serial_num(&self) -> &[u8] {
+        self.io.serial_num()
+    }
+}
+
+pub(crate) fn status_from_recv(result: &Result<usize>) -> StatusCode {
+    match result {
+        Ok(_) => StatusCode::Success,
+        Err(Error::Opal(OpalError::Status(code))) => *code,
+        Err(_) => StatusCode::Fail,
+    }
+}
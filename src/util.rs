@@ -0,0 +1,9 @@
+use core::time::Duration;
+
+/// Stalls for `duration` using the global boot services table stashed by
+/// `uefi_services::init`. Safe to call before `run` even sets anything up
+/// further, which is why `main` uses it directly around the error path.
+pub fn sleep(duration: Duration) {
+    let st = unsafe { uefi_services::system_table().as_ref() };
+    st.boot_services().stall(duration.as_micros() as usize);
+}